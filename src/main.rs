@@ -1,19 +1,30 @@
+use std::collections::HashMap;
 use std::env;
 use actix_web::*;
 use actix_web::http::header;
 use actix_cors::Cors;
-use std::collections::HashMap;
-use std::sync::Mutex;
 use reqwest;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
 use tokio::task;
 use std::error::Error;
 use std::fmt;
+mod middleware;
 mod services;
 use services::analyzer;
+use services::backends::{self, AiBackend};
+use services::cache::TtlCache;
 use services::utils;
 
+/// Default upper bound (used when `AI_BATCH_CONCURRENCY` is unset or not a
+/// number) on concurrent AI-provider requests a single `/analyze/batch`
+/// call may have in flight, so we don't blow through the provider's rate
+/// limit when a large comment thread arrives at once.
+const DEFAULT_BATCH_CONCURRENCY: usize = 5;
+
 struct AppState {
-    cache: Mutex<HashMap<String, analyzer::AnalyzeResponse>>,
+    cache: TtlCache<analyzer::AnalyzeResponse>,
+    backend: Box<dyn AiBackend>,
 }
 
 #[derive(Debug, Clone)]
@@ -60,22 +71,22 @@ async fn hello() -> impl Responder {
 async fn analyze(req: web::Json<analyzer::AnalyzeRequest>, data: web::Data<AppState>) -> impl Responder {
     println!("Received comment: {}", req.comment);
 
-    let mut cached_data = data.cache.lock().unwrap();
     let comment_hash = utils::hash_comment(&req.comment);
-    if let Some(response) = cached_data.get(&comment_hash) {
+    if let Some(response) = data.cache.get(&comment_hash) {
         println!("Cache hit!");
-        return HttpResponse::Ok().json(response.clone());
+        return HttpResponse::Ok().json(response);
     }
 
+    let data_for_task = data.clone();
     let comment = req.comment.clone();
     let result = task::spawn(async move {
-        analyzer::analyze_comment(&comment).await
+        analyzer::analyze_comment(&comment, data_for_task.backend.as_ref()).await
     }).await.unwrap();
 
     match result {
         Ok(response) => {
             // Cache the response using the comment hash
-            cached_data.insert(utils::hash_comment(&req.comment).clone(), response.clone());
+            data.cache.insert(comment_hash, response.clone());
             HttpResponse::Ok().json(response)
         }
         Err(e) => {
@@ -85,6 +96,84 @@ async fn analyze(req: web::Json<analyzer::AnalyzeRequest>, data: web::Data<AppSt
     }
 }
 
+/// Dedups by comment hash, serves cache hits immediately, and analyzes the
+/// remaining unique comments concurrently bounded by `AI_BATCH_CONCURRENCY`.
+///
+/// This is one `classify` call per pending comment, not one model request
+/// for the whole pending set — see the note on [`AiBackend`] for why. It
+/// still turns N sequential HTTP round-trips into N concurrent ones, which
+/// is the bulk of the latency win; true request-level packing is left for
+/// a backend that actually supports batched structured output.
+#[post("/analyze/batch")]
+async fn analyze_batch(req: web::Json<analyzer::BatchAnalyzeRequest>, data: web::Data<AppState>) -> impl Responder {
+    println!("Received batch of {} comments", req.comments.len());
+
+    let hashes: Vec<String> = req.comments.iter().map(|c| utils::hash_comment(c)).collect();
+
+    // Dedup by hash, keeping only the first copy of each unique comment for analysis.
+    let mut unique_comments: HashMap<String, String> = HashMap::new();
+    for (hash, comment) in hashes.iter().zip(req.comments.iter()) {
+        unique_comments.entry(hash.clone()).or_insert_with(|| comment.clone());
+    }
+
+    let mut resolved: HashMap<String, Result<analyzer::AnalyzeResponse, String>> = HashMap::new();
+    let mut pending = Vec::new();
+    for (hash, comment) in unique_comments {
+        if let Some(response) = data.cache.get(&hash) {
+            resolved.insert(hash, Ok(response));
+        } else {
+            pending.push((hash, comment));
+        }
+    }
+
+    let concurrency = env::var("AI_BATCH_CONCURRENCY")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_BATCH_CONCURRENCY);
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+
+    let mut tasks = Vec::with_capacity(pending.len());
+    for (hash, comment) in pending {
+        let data = data.clone();
+        let semaphore = semaphore.clone();
+        tasks.push(task::spawn(async move {
+            let _permit = semaphore.acquire_owned().await.unwrap();
+            let result = analyzer::analyze_comment(&comment, data.backend.as_ref()).await;
+            (hash, result)
+        }));
+    }
+
+    // A single failed comment must not void the results for the rest of the
+    // batch, so per-item errors are collected instead of aborting early.
+    for task in tasks {
+        let (hash, result) = task.await.unwrap();
+        match result {
+            Ok(response) => {
+                data.cache.insert(hash.clone(), response.clone());
+                resolved.insert(hash, Ok(response));
+            }
+            Err(e) => {
+                println!("Error analyzing comment in batch: {}", e);
+                resolved.insert(hash, Err(e.to_string()));
+            }
+        }
+    }
+
+    let responses: Vec<analyzer::BatchItemResponse> = req
+        .comments
+        .iter()
+        .zip(hashes.iter())
+        .map(|(comment, hash)| {
+            match resolved.get(hash).cloned().expect("every hash was resolved above") {
+                Ok(response) => analyzer::BatchItemResponse::ok(comment.clone(), response),
+                Err(error) => analyzer::BatchItemResponse::error(comment.clone(), error),
+            }
+        })
+        .collect();
+
+    HttpResponse::Ok().json(responses)
+}
+
 #[get("/health")]
 async fn health() -> impl Responder {
     HttpResponse::Ok().body("Health")
@@ -92,11 +181,10 @@ async fn health() -> impl Responder {
 
 #[get("/cache/{keyword}")]
 async fn cache(keyword: web::Path<String>, data: web::Data<AppState>) -> impl Responder {
-    let cached_data = data.cache.lock().unwrap();
     let keyword_str = keyword.into_inner();
-    if let Some(response) = cached_data.get(&keyword_str) {
+    if let Some(response) = data.cache.get(&keyword_str) {
         println!("Cache hit for keyword: {}", keyword_str);
-        HttpResponse::Ok().json(response.clone())
+        HttpResponse::Ok().json(response)
     } else {
         println!("Cache miss for keyword: {}", keyword_str);
         HttpResponse::NotFound().body("Cache miss")
@@ -107,16 +195,15 @@ async fn cache(keyword: web::Path<String>, data: web::Data<AppState>) -> impl Re
 async fn main() -> std::io::Result<()> {
     dotenv::dotenv().ok();
 
-    let gemini_token = env::var("AI_TOKEN_GEMINI").expect("AI_TOKEN_GEMINI not found in .env");
     let api_port = env::var("API_PORT").expect("API_PORT not found in .env");
     let environment = env::var("ENVIRONMENT").expect("ENVIRONMENT not found in .env");
 
-    println!("Gemini Token: {}", gemini_token);
     println!("API Port: {}", api_port);
     println!("Environment: {}", environment);
 
     let app_state = web::Data::new(AppState {
-        cache: Mutex::new(HashMap::new()),
+        cache: TtlCache::from_env("CACHE_PERSIST_PATH"),
+        backend: backends::build_backend(),
     });
 
     HttpServer::new(move || {
@@ -127,9 +214,11 @@ async fn main() -> std::io::Result<()> {
         .max_age(3600);
         App::new()
             .wrap(cors)
+            .wrap(actix_web::middleware::from_fn(middleware::security_headers))
             .app_data(app_state.clone())
             .service(hello)
             .service(analyze)
+            .service(analyze_batch)
             .service(health)
             .service(web::scope("/cache")
                 .app_data(app_state.clone())
@@ -0,0 +1,81 @@
+use actix_web::body::{to_bytes, BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::{Error, HttpResponse};
+
+/// Routes whose responses are effectively immutable once computed and can
+/// be cached long-term, with an `ETag` derived from the body.
+const LONG_LIVED_PREFIXES: &[&str] = &["/cache"];
+
+fn insert_security_headers(headers: &mut HeaderMap) {
+    headers.insert(
+        HeaderName::from_static("x-content-type-options"),
+        HeaderValue::from_static("nosniff"),
+    );
+    headers.insert(
+        HeaderName::from_static("x-frame-options"),
+        HeaderValue::from_static("DENY"),
+    );
+    headers.insert(
+        HeaderName::from_static("referrer-policy"),
+        HeaderValue::from_static("no-referrer"),
+    );
+}
+
+/// Injects security headers and a per-route `Cache-Control` policy on every
+/// response: long-lived + an `ETag` for successful `/cache/*` responses (a
+/// verdict is immutable once computed), `no-store` everywhere else —
+/// including a `/cache/*` miss, since that 404 can flip to a hit as soon as
+/// the keyword is analyzed and must not be cached by clients/CDNs in the
+/// meantime. CORS preflight `OPTIONS` requests pass through untouched so
+/// they don't pick up caching semantics meant for real hits.
+pub async fn security_headers(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    if req.method() == Method::OPTIONS {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    }
+
+    let path = req.path().to_string();
+    let (http_req, response) = next.call(req).await?.map_into_boxed_body().into_parts();
+
+    let is_long_lived = LONG_LIVED_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+        && response.status().is_success();
+
+    if is_long_lived {
+        let status = response.status();
+        let mut headers = response.headers().clone();
+        let bytes = to_bytes(response.into_body()).await.unwrap_or_default();
+        let etag = format!("\"{}\"", blake3::hash(&bytes).to_hex());
+
+        insert_security_headers(&mut headers);
+        headers.insert(
+            HeaderName::from_static("cache-control"),
+            HeaderValue::from_static("public, max-age=86400, immutable"),
+        );
+        headers.insert(
+            HeaderName::from_static("etag"),
+            HeaderValue::from_str(&etag).unwrap_or_else(|_| HeaderValue::from_static("")),
+        );
+
+        let mut builder = HttpResponse::build(status);
+        for (name, value) in headers.iter() {
+            builder.insert_header((name.clone(), value.clone()));
+        }
+        return Ok(ServiceResponse::new(http_req, builder.body(bytes)));
+    }
+
+    let mut response = response;
+    insert_security_headers(response.headers_mut());
+    response.headers_mut().insert(
+        HeaderName::from_static("cache-control"),
+        HeaderValue::from_static("no-store"),
+    );
+
+    Ok(ServiceResponse::new(http_req, response))
+}
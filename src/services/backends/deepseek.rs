@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use reqwest::Client;
+use std::env;
+
+use crate::services::analyzer::{AnalyzeResponse, CustomError};
+
+use super::{openai_json_schema_response_format, parse_structured_response, AiBackend};
+
+lazy_static! {
+    static ref DEEPSEEK_TOKEN: String =
+        env::var("AI_TOKEN_DEEPSEEK").expect("AI_TOKEN_DEEPSEEK not found in .env");
+}
+
+const DEEPSEEK_ENDPOINT: &str = "https://api.deepseek.com/chat/completions";
+
+pub struct DeepSeekBackend {
+    client: Client,
+}
+
+impl DeepSeekBackend {
+    pub fn new() -> Self {
+        DeepSeekBackend {
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl AiBackend for DeepSeekBackend {
+    async fn classify(&self, prompt: &str, comment: &str) -> Result<AnalyzeResponse, CustomError> {
+        let body = serde_json::json!({
+            "model": "deepseek-chat",
+            "messages": [
+                { "role": "system", "content": prompt },
+                { "role": "user", "content": comment }
+            ],
+            "stream": false,
+            "max_tokens": 50,
+            "response_format": openai_json_schema_response_format()
+        });
+
+        let response = self
+            .client
+            .post(DEEPSEEK_ENDPOINT)
+            .header("authorization", format!("Bearer {}", DEEPSEEK_TOKEN.clone()))
+            .header("content-type", "application/json")
+            .json(&body)
+            .send()
+            .await?;
+
+        let api_response = response.text().await?;
+        println!("DeepSeek API response: {}", api_response);
+
+        let parsed: serde_json::Value = serde_json::from_str(&api_response)?;
+        let choices = parsed["choices"].as_array().ok_or("Invalid response format")?;
+        if choices.is_empty() {
+            return Err("No choices found in response".into());
+        }
+        let content = choices[0]["message"]["content"]
+            .as_str()
+            .ok_or("Invalid content format")?;
+
+        parse_structured_response(content)
+    }
+}
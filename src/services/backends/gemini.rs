@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use reqwest::Client;
+use std::env;
+
+use crate::services::analyzer::{AnalyzeResponse, CustomError};
+
+use super::{parse_structured_response, AiBackend};
+
+lazy_static! {
+    static ref GEMINI_TOKEN: String =
+        env::var("AI_TOKEN_GEMINI").expect("AI_TOKEN_GEMINI not found in .env");
+}
+
+const GEMINI_ENDPOINT: &str =
+    "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.0-flash-lite:generateContent";
+
+pub struct GeminiBackend {
+    client: Client,
+}
+
+impl GeminiBackend {
+    pub fn new() -> Self {
+        GeminiBackend {
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl AiBackend for GeminiBackend {
+    async fn classify(&self, prompt: &str, comment: &str) -> Result<AnalyzeResponse, CustomError> {
+        let body = serde_json::json!({
+            "system_instruction": { "parts": [{ "text": prompt }] },
+            "contents": [{ "parts": [{ "text": comment }] }],
+            "generationConfig": {
+                "stopSequences": ["\n"],
+                "temperature": 0.2,
+                "maxOutputTokens": 50,
+                "topP": 0.5,
+                "topK": 3,
+                "responseMimeType": "application/json",
+                "responseSchema": {
+                    "type": "OBJECT",
+                    "properties": {
+                        "spam": { "type": "BOOLEAN" },
+                        "keyword": { "type": "STRING" },
+                        "confidence": { "type": "NUMBER" }
+                    },
+                    "required": ["spam", "keyword", "confidence"]
+                }
+            }
+        });
+
+        let response = self
+            .client
+            .post(GEMINI_ENDPOINT)
+            .header("Content-Type", "application/json")
+            .query(&[("key", GEMINI_TOKEN.clone())])
+            .json(&body)
+            .send()
+            .await?;
+
+        let api_response = response.text().await?;
+        println!("Gemini API response: {}", api_response);
+
+        let parsed: serde_json::Value = serde_json::from_str(&api_response)?;
+        let candidates = parsed["candidates"]
+            .as_array()
+            .ok_or("Invalid response format")?;
+        if candidates.is_empty() {
+            return Err("No candidates found in response".into());
+        }
+        let content = candidates[0]["content"]["parts"][0]["text"]
+            .as_str()
+            .ok_or("Invalid content format")?;
+
+        parse_structured_response(content)
+    }
+}
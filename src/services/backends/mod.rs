@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use std::env;
+
+use crate::services::analyzer::{AnalyzeResponse, CustomError};
+
+mod deepseek;
+mod gemini;
+mod openai_compatible;
+
+pub use deepseek::DeepSeekBackend;
+pub use gemini::GeminiBackend;
+pub use openai_compatible::OpenAiCompatibleBackend;
+
+/// A pluggable spam-classification backend.
+///
+/// Each implementation owns its endpoint URL, auth header construction,
+/// request-body builder and response extractor, so adding a new model is a
+/// new file rather than another arm in an `if` chain.
+///
+/// `classify` is one comment per call. `/analyze/batch` fans these out
+/// concurrently (bounded by a semaphore) rather than packing several
+/// comments into a single model request — no backend here exposes a
+/// multi-input structured-output call yet. That's an interim trade-off,
+/// not the end state: a provider that supports batched structured output
+/// would need its own `classify`-like method taking `&[String]` and
+/// returning one `AnalyzeResponse` per input, with `analyze_batch` grouping
+/// pending comments into provider-sized chunks instead of one task each.
+#[async_trait]
+pub trait AiBackend: Send + Sync {
+    async fn classify(&self, prompt: &str, comment: &str) -> Result<AnalyzeResponse, CustomError>;
+}
+
+/// Select the active backend once at startup from the `AI_SERVICE` env var.
+pub fn build_backend() -> Box<dyn AiBackend> {
+    let service = env::var("AI_SERVICE").unwrap_or_else(|_| "deepseek".to_string());
+    match service.to_lowercase().as_str() {
+        "gemini" => Box::new(GeminiBackend::new()),
+        "openai" | "tgi" | "self-hosted" => Box::new(OpenAiCompatibleBackend::new()),
+        _ => Box::new(DeepSeekBackend::new()),
+    }
+}
+
+/// Parse a model response that was forced into the `AnalyzeResponse` shape
+/// via structured output / function-calling, so the backend gets reliable
+/// machine-readable fields rather than scraping free text.
+pub(crate) fn parse_structured_response(content: &str) -> Result<AnalyzeResponse, CustomError> {
+    Ok(serde_json::from_str(content)?)
+}
+
+/// OpenAI-style `response_format` forcing the model's message content into
+/// the `AnalyzeResponse` JSON schema. Shared by the OpenAI-compatible chat
+/// backends (DeepSeek, self-hosted TGI, ...).
+pub(crate) fn openai_json_schema_response_format() -> serde_json::Value {
+    serde_json::json!({
+        "type": "json_schema",
+        "json_schema": {
+            "name": "analyze_response",
+            "schema": {
+                "type": "object",
+                "properties": {
+                    "spam": { "type": "boolean" },
+                    "keyword": { "type": "string" },
+                    "confidence": { "type": "number" }
+                },
+                "required": ["spam", "keyword", "confidence"]
+            }
+        }
+    })
+}
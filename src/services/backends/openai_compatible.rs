@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use reqwest::Client;
+use std::env;
+
+use crate::services::analyzer::{AnalyzeResponse, CustomError};
+
+use super::{openai_json_schema_response_format, parse_structured_response, AiBackend};
+
+lazy_static! {
+    static ref OPENAI_ENDPOINT: String = env::var("AI_ENDPOINT_OPENAI")
+        .unwrap_or_else(|_| "http://localhost:8080/v1/chat/completions".to_string());
+    static ref OPENAI_MODEL: String =
+        env::var("AI_MODEL_OPENAI").unwrap_or_else(|_| "local-model".to_string());
+    static ref OPENAI_TOKEN: Option<String> = env::var("AI_TOKEN_OPENAI").ok();
+}
+
+/// Any OpenAI-compatible chat-completions endpoint, e.g. a self-hosted
+/// Text Generation Inference (TGI) server, behind the same interface as the
+/// hosted providers.
+pub struct OpenAiCompatibleBackend {
+    client: Client,
+}
+
+impl OpenAiCompatibleBackend {
+    pub fn new() -> Self {
+        OpenAiCompatibleBackend {
+            client: Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl AiBackend for OpenAiCompatibleBackend {
+    async fn classify(&self, prompt: &str, comment: &str) -> Result<AnalyzeResponse, CustomError> {
+        let body = serde_json::json!({
+            "model": OPENAI_MODEL.clone(),
+            "messages": [
+                { "role": "system", "content": prompt },
+                { "role": "user", "content": comment }
+            ],
+            "stream": false,
+            "max_tokens": 50,
+            "response_format": openai_json_schema_response_format()
+        });
+
+        let mut request = self
+            .client
+            .post(OPENAI_ENDPOINT.clone())
+            .header("content-type", "application/json");
+        if let Some(token) = OPENAI_TOKEN.as_ref() {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.json(&body).send().await?;
+
+        let api_response = response.text().await?;
+        println!("OpenAI-compatible API response: {}", api_response);
+
+        let parsed: serde_json::Value = serde_json::from_str(&api_response)?;
+        let choices = parsed["choices"].as_array().ok_or("Invalid response format")?;
+        if choices.is_empty() {
+            return Err("No choices found in response".into());
+        }
+        let content = choices[0]["message"]["content"]
+            .as_str()
+            .ok_or("Invalid content format")?;
+
+        parse_structured_response(content)
+    }
+}
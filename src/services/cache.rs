@@ -0,0 +1,239 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task;
+
+const DEFAULT_TTL_SECS: u64 = 24 * 60 * 60;
+const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// How many inserts to batch before flushing to disk, so a burst of writes
+/// (e.g. a `/analyze/batch` call) doesn't re-serialize and rewrite the whole
+/// cache file on every single one.
+const PERSIST_EVERY_N_INSERTS: usize = 32;
+
+/// Hard cap on how many entries a single `find` call scans, so an expensive
+/// per-entry predicate can't blow past a fixed latency budget as the cache
+/// fills up toward `max_entries`.
+const MAX_SCAN_ENTRIES: usize = 500;
+
+struct Entry<V> {
+    value: V,
+    inserted_at: SystemTime,
+}
+
+/// A bounded, TTL-aware cache with LRU capacity eviction and optional,
+/// debounced write-through persistence to disk.
+///
+/// Replaces the plain `Mutex<HashMap<...>>` caches that grew forever and
+/// never expired a stale AI verdict. Expired entries are treated as misses
+/// and evicted lazily on lookup; once `max_entries` is exceeded the least
+/// recently used entry is evicted.
+pub struct TtlCache<V> {
+    entries: Mutex<HashMap<String, Entry<V>>>,
+    recency: Mutex<VecDeque<String>>,
+    ttl: Duration,
+    max_entries: usize,
+    persist_path: Option<String>,
+    inserts_since_persist: AtomicUsize,
+}
+
+impl<V: Clone + Serialize + DeserializeOwned> TtlCache<V> {
+    pub fn new(ttl: Duration, max_entries: usize, persist_path: Option<String>) -> Self {
+        let cache = TtlCache {
+            entries: Mutex::new(HashMap::new()),
+            recency: Mutex::new(VecDeque::new()),
+            ttl,
+            max_entries,
+            persist_path,
+            inserts_since_persist: AtomicUsize::new(0),
+        };
+        cache.load_from_disk();
+        cache
+    }
+
+    /// Build a cache from `CACHE_TTL_SECS` / `CACHE_MAX_ENTRIES`, with an
+    /// optional write-through persistence file at `persist_path_env`.
+    pub fn from_env(persist_path_env: &str) -> Self {
+        let ttl_secs = env::var("CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_TTL_SECS);
+        let max_entries = env::var("CACHE_MAX_ENTRIES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_ENTRIES);
+        let persist_path = env::var(persist_path_env).ok();
+
+        Self::new(Duration::from_secs(ttl_secs), max_entries, persist_path)
+    }
+
+    fn load_from_disk(&self) {
+        let Some(path) = &self.persist_path else { return };
+        let Ok(raw) = fs::read_to_string(path) else { return };
+        let Ok(records) = serde_json::from_str::<Vec<(String, V, u64)>>(&raw) else { return };
+
+        let mut entries = self.entries.lock().unwrap();
+        let mut recency = self.recency.lock().unwrap();
+        for (key, value, inserted_at_secs) in records {
+            let inserted_at = UNIX_EPOCH + Duration::from_secs(inserted_at_secs);
+            if inserted_at.elapsed().map(|age| age < self.ttl).unwrap_or(false) {
+                recency.push_back(key.clone());
+                entries.insert(key, Entry { value, inserted_at });
+            }
+        }
+    }
+
+    /// Serialize the current entries and flush them to `persist_path` off
+    /// the calling (async executor) thread. Called on a debounce in
+    /// [`TtlCache::insert`] rather than on every single insert, since a
+    /// batch of inserts would otherwise re-serialize and rewrite the whole
+    /// cache file once per item.
+    fn persist(&self) {
+        let Some(path) = self.persist_path.clone() else { return };
+        let entries = self.entries.lock().unwrap();
+        let records: Vec<(String, V, u64)> = entries
+            .iter()
+            .map(|(key, entry)| {
+                let secs = entry
+                    .inserted_at
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                (key.clone(), entry.value.clone(), secs)
+            })
+            .collect();
+        drop(entries);
+
+        let Ok(json) = serde_json::to_string(&records) else { return };
+        task::spawn_blocking(move || {
+            if let Err(e) = fs::write(&path, json) {
+                println!("Failed to persist cache to {}: {}", path, e);
+            }
+        });
+    }
+
+    fn is_expired(&self, inserted_at: SystemTime) -> bool {
+        inserted_at.elapsed().map(|age| age > self.ttl).unwrap_or(false)
+    }
+
+    fn touch(&self, key: &str) {
+        let mut recency = self.recency.lock().unwrap();
+        recency.retain(|k| k != key);
+        recency.push_back(key.to_string());
+    }
+
+    fn evict_expired_locked(
+        entries: &mut HashMap<String, Entry<V>>,
+        recency: &mut VecDeque<String>,
+        ttl: Duration,
+    ) {
+        let expired: Vec<String> = entries
+            .iter()
+            .filter(|(_, entry)| entry.inserted_at.elapsed().map(|age| age > ttl).unwrap_or(false))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in expired {
+            entries.remove(&key);
+            recency.retain(|k| k != &key);
+        }
+    }
+
+    fn evict_over_capacity(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        let mut recency = self.recency.lock().unwrap();
+        while entries.len() > self.max_entries {
+            match recency.pop_front() {
+                Some(oldest) => {
+                    entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Look up `key`, treating an expired entry as a miss and evicting it.
+    pub fn get(&self, key: &str) -> Option<V> {
+        let value = {
+            let mut entries = self.entries.lock().unwrap();
+            match entries.get(key) {
+                Some(entry) if self.is_expired(entry.inserted_at) => {
+                    entries.remove(key);
+                    None
+                }
+                Some(entry) => Some(entry.value.clone()),
+                None => None,
+            }
+        };
+
+        if value.is_some() {
+            self.touch(key);
+        }
+        value
+    }
+
+    /// Evict every expired entry in the cache, then scan at most
+    /// `MAX_SCAN_ENTRIES` of the most recently used entries for the first
+    /// one matching `predicate`.
+    ///
+    /// `predicate` can be expensive per call (e.g. fuzzy matching), so this
+    /// deliberately does not scan the whole cache once `max_entries` fills
+    /// up — that would mean up to `max_entries` predicate calls on an
+    /// actix worker thread per lookup. Scanning most-recently-used first
+    /// also means a hot keyword stays cheap to find even when the cache is
+    /// at capacity.
+    pub fn find<F: Fn(&str, &V) -> bool>(&self, predicate: F) -> Option<(String, V)> {
+        let mut entries = self.entries.lock().unwrap();
+        let mut recency = self.recency.lock().unwrap();
+        Self::evict_expired_locked(&mut entries, &mut recency, self.ttl);
+
+        let found = recency
+            .iter()
+            .rev()
+            .take(MAX_SCAN_ENTRIES)
+            .find_map(|key| {
+                entries
+                    .get(key)
+                    .filter(|entry| predicate(key, &entry.value))
+                    .map(|entry| (key.clone(), entry.value.clone()))
+            });
+
+        if let Some((key, _)) = &found {
+            recency.retain(|k| k != key);
+            recency.push_back(key.clone());
+        }
+
+        found
+    }
+
+    /// Insert or overwrite `key`, refreshing its insertion timestamp and
+    /// evicting over-capacity entries. Persistence to disk, if configured,
+    /// is debounced to every `PERSIST_EVERY_N_INSERTS` inserts rather than
+    /// done on every call.
+    pub fn insert(&self, key: String, value: V) {
+        {
+            let mut entries = self.entries.lock().unwrap();
+            entries.insert(
+                key.clone(),
+                Entry {
+                    value,
+                    inserted_at: SystemTime::now(),
+                },
+            );
+        }
+        self.touch(&key);
+        self.evict_over_capacity();
+
+        if self.persist_path.is_some() {
+            let pending = self.inserts_since_persist.fetch_add(1, Ordering::Relaxed) + 1;
+            if pending >= PERSIST_EVERY_N_INSERTS {
+                self.inserts_since_persist.store(0, Ordering::Relaxed);
+                self.persist();
+            }
+        }
+    }
+}
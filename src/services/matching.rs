@@ -0,0 +1,72 @@
+/// Keywords shorter than this are too short to fuzzy-match meaningfully —
+/// an empty or near-empty keyword would `contains()`-match almost any
+/// comment, so both the cache fast-path and cache inserts reject them.
+pub const MIN_KEYWORD_LEN: usize = 3;
+
+/// Typo-tolerant substring search used by the `KEYWORD_CACHE` fast-path.
+///
+/// Does a fast exact substring check first; on miss, slides windows of
+/// length `len(keyword)` (and `len` +/- 1) across `haystack` and accepts a
+/// match on the first window whose Levenshtein distance to `keyword` is
+/// within a length-derived tolerance `k = max(1, len(keyword) / 6)`. Catches
+/// spammers who insert separators or swap characters (`m-a-n-d-a-l-i-k-a`,
+/// `mandaIika`) without scanning the whole comment at full edit-distance.
+pub fn fuzzy_contains(haystack: &str, keyword: &str) -> bool {
+    let keyword_chars: Vec<char> = keyword.chars().collect();
+    let len = keyword_chars.len();
+    if len < MIN_KEYWORD_LEN {
+        return false;
+    }
+
+    if haystack.contains(keyword) {
+        return true;
+    }
+
+    let haystack_chars: Vec<char> = haystack.chars().collect();
+
+    let k = (len / 6).max(1);
+
+    for window_len in [len.saturating_sub(1), len, len + 1] {
+        if window_len == 0 || window_len > haystack_chars.len() {
+            continue;
+        }
+        for start in 0..=(haystack_chars.len() - window_len) {
+            let window = &haystack_chars[start..start + window_len];
+            if let Some(distance) = bounded_edit_distance(window, &keyword_chars, k) {
+                if distance <= k {
+                    return true;
+                }
+            }
+        }
+    }
+
+    false
+}
+
+/// Classic DP-row Levenshtein distance that aborts early once the running
+/// minimum of a row exceeds `max_k`, returning `None` in that case.
+fn bounded_edit_distance(a: &[char], b: &[char], max_k: usize) -> Option<usize> {
+    let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+    let mut curr_row = vec![0usize; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr_row[0] = i;
+        let mut row_min = curr_row[0];
+
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            curr_row[j] = (prev_row[j] + 1)
+                .min(curr_row[j - 1] + 1)
+                .min(prev_row[j - 1] + cost);
+            row_min = row_min.min(curr_row[j]);
+        }
+
+        if row_min > max_k {
+            return None;
+        }
+
+        std::mem::swap(&mut prev_row, &mut curr_row);
+    }
+
+    Some(prev_row[b.len()])
+}
@@ -0,0 +1,5 @@
+pub mod analyzer;
+pub mod backends;
+pub mod cache;
+pub mod matching;
+pub mod utils;